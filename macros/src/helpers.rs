@@ -1,3 +1,4 @@
+use darling::ast::{Data, Style};
 use darling::{FromDeriveInput, ToTokens};
 use heck::SnekCase;
 use proc_macro2::TokenStream;
@@ -14,6 +15,11 @@ impl ToTokens for Assoc {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let name = &self.ident;
         let id = self.id;
+        if id == 0 {
+            let err = syn::Error::new(self.ident.span(), "assoc id must be non-zero");
+            tokens.extend(err.to_compile_error());
+            return;
+        }
         let assoc_name = syn::Ident::new(&format!("Assoc{}", &self.ident), self.ident.span());
         let fn_name = syn::Ident::new(&name.to_string().to_snek_case(), self.ident.span());
         let new_stuff = quote! {
@@ -27,29 +33,57 @@ impl ToTokens for Assoc {
                 fn #fn_name<Ent: ::wtf::Entity>(&self, what: &Ent) -> #name<::wtf::Dirty>;
             }
 
-            impl ::wtf::Save<()> for #name<::wtf::Dirty> {
-                type Saved = #name<::wtf::Saved<()>>;
+            impl #name<::wtf::Dirty> {
+                /// Human readable name for the assoc type. Lives on the `Dirty` state
+                /// only, so it can be called without naming the typestate.
+                pub fn name() -> &'static str {
+                    stringify!(#name)
+                }
 
-                fn save(self, db: &mut dyn ::wtf::TeaConnection) -> Result<Self::Saved, ::wtf::SaveError<Self>> {
+                /// Save the assoc with `data` as its payload
+                pub fn save_with_data(self, data: &[u8], db: &mut dyn ::wtf::TeaConnection) -> Result<#name<::wtf::Saved<()>>, ::wtf::SaveError<Self>> {
                     let Self(assoc, _) = self;
                     let (from, to, ty) = assoc.split();
-                    if let Err(e) = db.assoc_add(ty, from.id(), to.id(), &[]) {
+                    if let Err(e) = db.assoc_add(ty, from.id(), to.id(), data) {
                         return Err(::wtf::SaveError::Tea(self, e));
                     }
                     Ok(#name(
-                        ::wtf::RawAssoc::new(from, to, ty.as_u64()),
-                        ::wtf::Saved::new(()),
+                        ::wtf::RawAssoc::new(from, to, ty),
+                        ().into(),
                     ))
                 }
             }
 
+            impl ::wtf::Save<()> for #name<::wtf::Dirty> {
+                type Saved = #name<::wtf::Saved<()>>;
+
+                fn save(self, db: &mut dyn ::wtf::TeaConnection) -> Result<Self::Saved, ::wtf::SaveError<Self>> {
+                    self.save_with_data(&[], db)
+                }
+            }
+
+            impl<S: ::wtf::PersistedState> ::std::fmt::Display for #name<S> {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    let (from, to, _) = self.0.split();
+                    write!(
+                        f,
+                        "{}({}) -[{}]-> {}({})",
+                        from.ty().as_u64(),
+                        from.id().as_u64(),
+                        #name::<::wtf::Dirty>::name(),
+                        to.ty().as_u64(),
+                        to.id().as_u64(),
+                    )
+                }
+            }
+
             impl<T> #assoc_name for T where T: ::wtf::Entity {
                 fn #fn_name<Ent: ::wtf::Entity>(&self, what: &Ent) -> #name<::wtf::Dirty> {
                     #name(
                         ::wtf::RawAssoc::new(
                             Self::entity(self),
                             Ent::entity(what),
-                            #id,
+                            ::wtf::AssocType::from_u64(#id).expect("checked non-zero by #[derive(Assoc)]"),
                         ),
                         ::wtf::Dirty,
                     )
@@ -64,8 +98,14 @@ impl ToTokens for Assoc {
 #[darling(attributes(entity))]
 pub struct Entity {
     ident: syn::Ident,
-    // data: Data<(), syn::Field>,
+    data: Data<(), syn::Field>,
     id: u64,
+    /// the user supplies their own `Validate` impl
+    #[darling(default)]
+    validate: bool,
+    /// emit a `Default` impl that defaults every field
+    #[darling(default)]
+    default: bool,
 }
 
 impl ToTokens for Entity {
@@ -73,7 +113,47 @@ impl ToTokens for Entity {
         let name = &self.ident;
         let id = self.id;
         let ent_name = syn::Ident::new(&format!("Ent{}", &self.ident), self.ident.span());
+        let validate_impl = if self.validate {
+            quote!()
+        } else {
+            quote! {
+                impl ::wtf::Validate for #name {}
+            }
+        };
+        let default_impl = if self.default {
+            let body = match &self.data {
+                Data::Struct(fields) => match fields.style {
+                    Style::Struct => {
+                        let names = fields.fields.iter().map(|f| &f.ident);
+                        quote!(Self { #(#names: ::std::default::Default::default()),* })
+                    }
+                    Style::Tuple => {
+                        let values = fields
+                            .fields
+                            .iter()
+                            .map(|_| quote!(::std::default::Default::default()));
+                        quote!(Self(#(#values),*))
+                    }
+                    Style::Unit => quote!(Self),
+                },
+                Data::Enum(_) => {
+                    quote!(compile_error!("#[entity(default)] is only supported on structs"))
+                }
+            };
+            quote! {
+                impl ::std::default::Default for #name {
+                    fn default() -> Self {
+                        #body
+                    }
+                }
+            }
+        } else {
+            quote!()
+        };
         let new_stuff = quote! {
+            #validate_impl
+            #default_impl
+
             #[derive(Debug)]
             pub struct #ent_name<S: ::wtf::PersistedState> {
                 ent: #name,
@@ -87,6 +167,23 @@ impl ToTokens for Entity {
                 }
             }
 
+            impl<S: ::wtf::PersistedState> #ent_name<S> {
+                /// Human readable name of the wrapped type, see `ToEntity::type_label`
+                pub fn type_name(&self) -> &'static str {
+                    <#name as ::wtf::ToEntity>::type_label()
+                }
+
+                /// Project a field out of the entity through `accessor`
+                pub fn get_field<V, F: Fn(&#name) -> &V>(&self, accessor: F) -> &V {
+                    accessor(&self.ent)
+                }
+
+                /// Like `get_field`, but hands back an owned copy of the field
+                pub fn get_field_cloned<V: Clone, F: Fn(&#name) -> &V>(&self, accessor: F) -> V {
+                    self.get_field(accessor).clone()
+                }
+            }
+
             impl From<#name> for #ent_name<::wtf::Dirty> {
                 fn from(t: #name) -> #ent_name<::wtf::Dirty> {
                     Self {
@@ -100,6 +197,9 @@ impl ToTokens for Entity {
                 type Saved = #ent_name<::wtf::Saved<::wtf::RawEntity>>;
 
                 fn save(self, db: &mut dyn ::tea::TeaConnection) -> ::tea::Result<Self::Saved, ::wtf::SaveError<Self>> {
+                    if let Err(msg) = ::wtf::Validate::validate(&self) {
+                        return Err(::wtf::SaveError::Validation(self, msg));
+                    }
                     let data = match serde_json::to_vec(&self) {
                         Ok(d) => d,
                         Err(e) => return Err(::wtf::SaveError::Serde(self, e)),
@@ -112,7 +212,7 @@ impl ToTokens for Entity {
                     let raw = ::wtf::RawEntity::new(id, ty);
                     Ok(#ent_name {
                         ent: self,
-                        db_state: ::wtf::Saved::new(raw),
+                        db_state: raw.into(),
                     })
                 }
             }
@@ -129,6 +229,39 @@ impl ToTokens for Entity {
                 }
             }
 
+            impl #ent_name<::wtf::Saved<::wtf::RawEntity>> {
+                /// Split the entity into its id and its data
+                pub fn into_pair(self) -> (::tea::EntityId, #name) {
+                    (self.db_state.as_ref().id(), self.ent)
+                }
+
+                /// Borrowing version of `into_pair`
+                pub fn as_pair(&self) -> (::tea::EntityId, &#name) {
+                    (self.db_state.as_ref().id(), &self.ent)
+                }
+            }
+
+            // saved entities are graph nodes, so they are ordered (and equal) by id alone
+            impl PartialEq for #ent_name<::wtf::Saved<::wtf::RawEntity>> {
+                fn eq(&self, other: &Self) -> bool {
+                    ::wtf::Entity::id(self) == ::wtf::Entity::id(other)
+                }
+            }
+
+            impl Eq for #ent_name<::wtf::Saved<::wtf::RawEntity>> {}
+
+            impl PartialOrd for #ent_name<::wtf::Saved<::wtf::RawEntity>> {
+                fn partial_cmp(&self, other: &Self) -> Option<::std::cmp::Ordering> {
+                    Some(self.cmp(other))
+                }
+            }
+
+            impl Ord for #ent_name<::wtf::Saved<::wtf::RawEntity>> {
+                fn cmp(&self, other: &Self) -> ::std::cmp::Ordering {
+                    ::wtf::Entity::id(self).cmp(&::wtf::Entity::id(other))
+                }
+            }
+
             #[automatically_derived]
             impl ::wtf::ToEntity for #name {
                 type Entity = #ent_name<::wtf::Dirty>;
@@ -143,6 +276,10 @@ impl ToTokens for Entity {
                         db_state: ::wtf::Dirty,
                     }
                 }
+
+                fn type_label() -> &'static str {
+                    stringify!(#name)
+                }
             }
         };
         tokens.extend(new_stuff)
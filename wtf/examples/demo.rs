@@ -1,9 +1,14 @@
 use macros::{Assoc, Entity};
 use rusqlite::DatabaseName;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use wtf::Entity as _;
 use wtf::Save;
+use wtf::SaveError;
 use wtf::TeaConnection;
 use wtf::ToEntity;
+use wtf::WtfResult;
 use wtf::{PersistedState, RawAssoc};
 
 #[derive(Assoc, Debug)]
@@ -38,17 +43,34 @@ pub struct Play {
 }
 
 #[derive(macros::Entity, Debug, Serialize, Deserialize)]
-#[entity(id = 13)]
+#[entity(id = 13, default)]
 pub struct Comment {
     text: String,
 }
 
 #[derive(macros::Entity, Debug, Serialize, Deserialize)]
-#[entity(id = 10)]
+#[entity(id = 14, default)]
+pub struct Tag(String, u32);
+
+#[derive(macros::Entity, Debug, Serialize, Deserialize)]
+#[entity(id = 15, default)]
+pub struct Bookmark;
+
+#[derive(macros::Entity, Debug, Serialize, Deserialize)]
+#[entity(id = 10, validate)]
 pub struct Person {
     name: String,
 }
 
+impl wtf::Validate for Person {
+    fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("a person needs a name".to_string());
+        }
+        Ok(())
+    }
+}
+
 impl Person {
     pub fn new(name: &str) -> Self {
         Self {
@@ -83,11 +105,34 @@ impl Play {
     }
 }
 
+/// Save a freshly defaulted entity of any type
+fn save_default<T>(db: &mut dyn TeaConnection) -> Result<T::Saved, wtf::SaveError<T>>
+where
+    T: Default + Save<wtf::RawEntity>,
+{
+    T::default().save(db)
+}
+
+fn id_of(e: impl wtf::Entity) -> wtf::EntityId {
+    e.id()
+}
+
+/// Saves and raw tea calls share one error type through `WtfResult`
+fn add_coauthor(
+    db: &mut dyn TeaConnection,
+    book: &EntBook<wtf::Saved<wtf::RawEntity>>,
+) -> WtfResult<()> {
+    let coauthor = Person::new("michael faraday").save(db)?;
+    let (from, to, ty) = coauthor.authored(book).0.split();
+    db.assoc_add(ty, from.id(), to.id(), &[])?;
+    Ok(())
+}
+
 fn main() -> anyhow::Result<()> {
     let mut db = rusqlite::Connection::open_in_memory()?;
     db.initialize()?;
     // The generated types aren't all that yucky
-    let person: EntPerson<wtf::Saved<wtf::RawEntity>> = Person::new("james maxwell").save(&mut db)?;
+    let mut person: EntPerson<wtf::Saved<wtf::RawEntity>> = Person::new("james maxwell").save(&mut db)?;
     let comment = Comment::new("buzz buzz").save(&mut db)?;
     let play = Play::new("so you think you can play", "this time its personal").save(&mut db)?;
     let book = Book::new(
@@ -102,6 +147,117 @@ fn main() -> anyhow::Result<()> {
     let book_author = book.authored_by(&person).save(&mut db)?;
 
     assert!(comment_author == play_author && play_author == book_author);
+
+    // the derived assoc carries a tea::AssocType, which goes straight into assoc_add
+    let (from, to, ty) = person.authored(&play).0.split();
+    db.assoc_add(ty, from.id(), to.id(), &[])?;
+
+    // assoc payloads go through save_with_data
+    let with_data = person.authored(&book).save_with_data(b"hello", &mut db)?;
+    let (from, to, ty) = with_data.0.split();
+    let stored = db.assoc_get(ty, from.id(), &[to.id()], None, None)?;
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].data, b"hello");
+
+    let person_id = person.id().as_u64();
+    let book_id = book.id().as_u64();
+    assert_eq!(
+        person.entity().to_string(),
+        format!("Entity(id={}, ty=10)", person_id)
+    );
+    assert_eq!(
+        with_data.0.to_string(),
+        format!("Assoc(ty=1: {} -> {})", person_id, book_id)
+    );
+    assert_eq!(
+        with_data.to_string(),
+        format!("10({}) -[Authored]-> 11({})", person_id, book_id)
+    );
+
+    add_coauthor(&mut db, &book)?;
+
+    // references to entities are entities too
+    let by_ref: &&EntPerson<wtf::Saved<wtf::RawEntity>> = &&person;
+    assert_eq!(id_of(by_ref), person.id());
+    assert_eq!(id_of(&mut person), person.id());
+
+    assert_eq!(person.type_name(), "Person");
+    assert_eq!(person.get_field(|p| &p.name), "james maxwell");
+    assert_eq!(person.get_field_cloned(|p| &p.name), "james maxwell");
+    let (id, data) = person.as_pair();
+    assert_eq!(id, person.id());
+    assert_eq!(data.name, "james maxwell");
+    let tesla = Person::new("nikola tesla").save(&mut db)?;
+    let tesla_id = tesla.id();
+    let (id, data) = tesla.into_pair();
+    assert_eq!(id, tesla_id);
+    assert_eq!(data.name, "nikola tesla");
+    assert_eq!(Authored::name(), "Authored");
+
+    // #[entity(default)] works for named, tuple and unit structs
+    assert!(Comment::default().text.is_empty());
+    let Tag(label, uses) = Tag::default();
+    assert!(label.is_empty() && uses == 0);
+    save_default::<Bookmark>(&mut db)?;
+
+    assert_eq!(*wtf::Saved::new(7u64).map(|n| n * 2).as_ref_inner(), 14);
+    let saved: wtf::Saved<u64> = 42u64.into();
+    assert_eq!(*saved.as_ref_inner(), 42);
+    let saved = wtf::Saved::<wtf::EntityId>::try_from(saved).expect("42 is a valid id");
+    assert_eq!(saved.as_ref_inner().as_u64(), 42);
+    assert_eq!(saved.into_id().as_u64(), 42);
+    assert!(wtf::Saved::<wtf::EntityId>::try_from(wtf::Saved::new(0u64)).is_err());
+
+    // entities with their own Validate impl are checked before anything is written
+    match Person::new("").save(&mut db) {
+        Err(SaveError::Validation(_, msg)) => assert_eq!(msg, "a person needs a name"),
+        other => panic!("expected a validation error, got {:?}", other),
+    }
+
+    // saved entities are equal and ordered by id alone, whatever order they come in
+    let mut people = Vec::new();
+    for name in &["ada", "grace", "edsger"] {
+        people.push(Person::new(name).save(&mut db)?);
+    }
+    let ids: Vec<_> = people.iter().map(|p| p.id()).collect();
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    let people: BTreeSet<_> = people.into_iter().rev().collect();
+    assert_eq!(people.iter().map(|p| p.id()).collect::<Vec<_>>(), ids);
+
+    let mut books = vec![
+        Book::new("a", "first").save(&mut db)?,
+        Book::new("b", "second").save(&mut db)?,
+        Book::new("c", "third").save(&mut db)?,
+    ];
+    let ids: Vec<_> = books.iter().map(|b| b.id()).collect();
+    books.reverse();
+    books.sort();
+    assert_eq!(books.iter().map(|b| b.id()).collect::<Vec<_>>(), ids);
+    assert!(books[0] != books[1]);
+
+    // a 5-person chain, walked head to tail
+    let mut chain = Vec::new();
+    for name in &["one", "two", "three", "four", "five"] {
+        chain.push(Person::new(name).save(&mut db)?);
+    }
+    for pair in chain.windows(2) {
+        pair[0].authored(&pair[1]).save(&mut db)?;
+    }
+    let chain: Vec<_> = chain.iter().map(|p| p.id()).collect();
+    let authored = wtf::AssocType::from_u64(1).expect("non-zero");
+    let path = wtf::graph::shortest_path(chain[0], chain[4], &[authored], &mut db)?;
+    assert_eq!(path, Some(chain.clone()));
+    let path = wtf::graph::shortest_path(chain[4], chain[0], &[authored], &mut db)?;
+    assert_eq!(path, None);
+
+    // boxed entities can live side by side
+    let expected = [(person.id(), 10), (book.id(), 11)];
+    let ents: Vec<Box<dyn wtf::Entity>> = vec![Box::new(person), Box::new(book)];
+    for (ent, (id, ty)) in ents.iter().zip(&expected) {
+        assert_eq!(id_of(ent), *id);
+        assert_eq!(ent.ty().as_u64(), *ty);
+    }
+
     db.backup(DatabaseName::Main, "thingy.sqlite", None)
         .unwrap();
     Ok(())
@@ -0,0 +1,59 @@
+//! Graph walks over assocs
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::{AssocRangeAfter, AssocRangeLimit, AssocType, EntityId, TeaConnection, TeaError};
+
+/// Find the minimum-hop path from `from` to `to`, following any of `assoc_types`.
+/// The returned path includes both ends, or is `None` if `to` can't be reached.
+pub fn shortest_path<DB: TeaConnection + ?Sized>(
+    from: EntityId,
+    to: EntityId,
+    assoc_types: &[AssocType],
+    db: &mut DB,
+) -> Result<Option<Vec<EntityId>>, TeaError> {
+    if from == to {
+        return Ok(Some(vec![from]));
+    }
+    // each node reached points back at the node it was first reached from
+    let mut parents: HashMap<EntityId, EntityId> = HashMap::new();
+    let mut queue = VecDeque::new();
+    queue.push_back(from);
+    while let Some(node) = queue.pop_front() {
+        for &ty in assoc_types {
+            let mut after = AssocRangeAfter::First;
+            loop {
+                let page = db.assoc_range(ty, node, after, AssocRangeLimit::Maximum)?;
+                let last = match page.last() {
+                    Some(assoc) => assoc.id2,
+                    None => break,
+                };
+                for assoc in page {
+                    let next = assoc.id2;
+                    if next == from || parents.contains_key(&next) {
+                        continue;
+                    }
+                    parents.insert(next, node);
+                    if next == to {
+                        return Ok(Some(path_to(to, &parents)));
+                    }
+                    queue.push_back(next);
+                }
+                after = AssocRangeAfter::ID(last);
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Walk the parent pointers back from `to` and flip them into a from -> to path
+fn path_to(to: EntityId, parents: &HashMap<EntityId, EntityId>) -> Vec<EntityId> {
+    let mut path = vec![to];
+    let mut node = to;
+    while let Some(&parent) = parents.get(&node) {
+        path.push(parent);
+        node = parent;
+    }
+    path.reverse();
+    path
+}
@@ -1,7 +1,11 @@
 //#![allow(unused)]
 
+pub mod graph;
+
 mod tea_reexports {
-    pub use tea::{AssocType, EntityId, EntityType, TeaError, TeaConnection};
+    pub use tea::{
+        AssocRangeAfter, AssocRangeLimit, AssocType, EntityId, EntityType, TeaError, TeaConnection,
+    };
 }
 
 pub use crate::tea_reexports::*;
@@ -18,6 +22,40 @@ impl<Id: std::fmt::Debug> Saved<Id> {
     pub fn new(id: Id) -> Self {
         Self(id)
     }
+
+    /// Convert the saved id into another representation.
+    /// The fallible `Saved<u64>` -> `Saved<EntityId>` conversion is a `TryFrom` instead.
+    pub fn map<U: std::fmt::Debug, F: FnOnce(Id) -> U>(self, f: F) -> Saved<U> {
+        Saved(f(self.0))
+    }
+
+    /// Get a reference to the saved id.
+    pub fn as_ref_inner(&self) -> &Id {
+        &self.0
+    }
+
+    /// Unwrap the saved id.
+    pub fn into_id(self) -> Id {
+        self.0
+    }
+}
+
+impl<Id: std::fmt::Debug> From<Id> for Saved<Id> {
+    fn from(id: Id) -> Self {
+        Self(id)
+    }
+}
+
+/// Zero is not a valid `EntityId`, in which case the original `Saved<u64>` is handed back.
+impl std::convert::TryFrom<Saved<u64>> for Saved<EntityId> {
+    type Error = Saved<u64>;
+
+    fn try_from(saved: Saved<u64>) -> Result<Self, Self::Error> {
+        match EntityId::from_u64(saved.0) {
+            Some(id) => Ok(Saved(id)),
+            None => Err(saved),
+        }
+    }
 }
 
 /// Marker trait for Ent typestates
@@ -48,12 +86,26 @@ impl RawEntity {
     }
 }
 
+impl std::fmt::Display for RawEntity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Entity(id={}, ty={})", self.id.as_u64(), self.ty.as_u64())
+    }
+}
+
 /// If you have a well known type ID, and are Serialize + Deserialize, you can become an Ent!
 pub trait ToEntity {
     type Entity;
 
     fn entity_type() -> EntityType;
     fn into_entity(self) -> Self::Entity;
+
+    /// Human readable name for the type, without the module path
+    fn type_label() -> &'static str {
+        let full = std::any::type_name::<Self>();
+        // drop generic arguments first, their paths have `::` in them too
+        let path = full.split('<').next().unwrap_or(full);
+        path.rsplit("::").next().unwrap_or(path)
+    }
 }
 
 /// An Entity consts of a grand total of 128 bits of data.
@@ -63,6 +115,49 @@ pub trait Entity {
     fn id(&self) -> EntityId;
     fn entity(&self) -> RawEntity;
 }
+
+impl<E: Entity + ?Sized> Entity for &E {
+    fn ty(&self) -> EntityType {
+        (**self).ty()
+    }
+
+    fn id(&self) -> EntityId {
+        (**self).id()
+    }
+
+    fn entity(&self) -> RawEntity {
+        (**self).entity()
+    }
+}
+
+impl<E: Entity + ?Sized> Entity for &mut E {
+    fn ty(&self) -> EntityType {
+        (**self).ty()
+    }
+
+    fn id(&self) -> EntityId {
+        (**self).id()
+    }
+
+    fn entity(&self) -> RawEntity {
+        (**self).entity()
+    }
+}
+
+impl<E: Entity + ?Sized> Entity for Box<E> {
+    fn ty(&self) -> EntityType {
+        (**self).ty()
+    }
+
+    fn id(&self) -> EntityId {
+        (**self).id()
+    }
+
+    fn entity(&self) -> RawEntity {
+        (**self).entity()
+    }
+}
+
 /// Storage of an assocation. If you think of an assocation as an arrow,
 /// then the base of the arrow is the "from" entity, and the "to" entity
 /// is being pointed at by the arrow.
@@ -77,13 +172,25 @@ pub struct RawAssoc {
 }
 
 impl RawAssoc {
-    pub fn new(from: RawEntity, to: RawEntity, ty: u64) -> Self { Self { from, to, ty: AssocType::from_u64(ty).unwrap() } }
+    pub fn new(from: RawEntity, to: RawEntity, ty: AssocType) -> Self { Self { from, to, ty } }
 
     pub fn split(&self) -> (RawEntity, RawEntity, AssocType) {
         (self.from, self.to, self.ty)
     }
 }
 
+impl std::fmt::Display for RawAssoc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Assoc(ty={}: {} -> {})",
+            self.ty.as_u64(),
+            self.from.id.as_u64(),
+            self.to.id.as_u64()
+        )
+    }
+}
+
 /// If you can tell me what you are and what 2 entities you describe, you can be an Assoc!
 pub trait Assoc {
     fn obj1(&self) -> RawEntity;
@@ -128,10 +235,39 @@ pub enum SaveError<T: std::fmt::Debug> {
     Serde(T, #[source] serde_json::Error),
     #[error("Database failure: {1}")]
     Tea(T, #[source] TeaError),
+    #[error("Validation failure: {1}")]
+    Validation(T, String),
 }
 
 pub type SaveResult<T> = std::result::Result<T, SaveError<T>>;
 
+/// Application-level error, so code mixing saves and raw tea calls can just `?` everything.
+/// The `SaveError`'s unsaved value is type erased along with it, so a `WtfError::Save`
+/// can no longer be matched on `SaveError::Validation` or `SaveError::Tea`.
+#[derive(Debug, thiserror::Error)]
+pub enum WtfError {
+    #[error(transparent)]
+    Save(Box<dyn std::error::Error + Send + Sync>),
+    #[error(transparent)]
+    Tea(#[from] TeaError),
+}
+
+impl<T: std::fmt::Debug + Send + Sync + 'static> From<SaveError<T>> for WtfError {
+    fn from(e: SaveError<T>) -> Self {
+        WtfError::Save(Box::new(e))
+    }
+}
+
+pub type WtfResult<T> = std::result::Result<T, WtfError>;
+
+/// Business rules checked before an entity is serialized and saved.
+/// `#[derive(Entity)]` provides the no-op impl unless `#[entity(validate)]` is given.
+pub trait Validate {
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
 /// Save is how the object -> database serialization goes
 pub trait Save<Id>: Sized + std::fmt::Debug {
     type Saved: Sized + std::fmt::Debug;